@@ -0,0 +1,117 @@
+use crate::tree::Entry;
+
+/// Sort order for the children displayed in the current directory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortBy {
+    Size,
+    Name,
+}
+
+/// Interactive navigation state: the full size tree, which directory is
+/// currently drilled into (`stack`, a path of child indices from the
+/// root), which row is highlighted, and how children are sorted.
+pub struct App {
+    root: Entry,
+    stack: Vec<usize>,
+    pub selected: usize,
+    pub sort_by: SortBy,
+}
+
+impl App {
+    pub fn new(root: Entry) -> Self {
+        App {
+            root,
+            stack: Vec::new(),
+            selected: 0,
+            sort_by: SortBy::Size,
+        }
+    }
+
+    /// The directory entry currently being viewed.
+    pub fn current(&self) -> &Entry {
+        let mut node = &self.root;
+        for &idx in &self.stack {
+            node = &node.children.as_ref().unwrap()[idx];
+        }
+        node
+    }
+
+    /// Indices into `current().children`, ordered per `sort_by`.
+    pub fn visible_order(&self) -> Vec<usize> {
+        let children = self
+            .current()
+            .children
+            .as_ref()
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        let mut order: Vec<usize> = (0..children.len()).collect();
+        match self.sort_by {
+            SortBy::Size => order.sort_by(|&a, &b| children[b].bytes.cmp(&children[a].bytes)),
+            SortBy::Name => order.sort_by(|&a, &b| children[a].name.cmp(&children[b].name)),
+        }
+        order
+    }
+
+    /// Slash-separated path from the root to the directory being viewed.
+    pub fn breadcrumb(&self) -> String {
+        let mut names = vec![self.root.name.clone()];
+        let mut node = &self.root;
+        for &idx in &self.stack {
+            node = &node.children.as_ref().unwrap()[idx];
+            names.push(node.name.clone());
+        }
+        names.join("/")
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let len = self.visible_order().len();
+        if len == 0 {
+            self.selected = 0;
+            return;
+        }
+        let next = (self.selected as isize + delta).max(0).min(len as isize - 1);
+        self.selected = next as usize;
+    }
+
+    /// Highlight the row at `row`, if it exists.
+    pub fn select(&mut self, row: usize) {
+        if row < self.visible_order().len() {
+            self.selected = row;
+        }
+    }
+
+    /// Descend into the highlighted entry, if it is a directory.
+    pub fn descend(&mut self) {
+        let order = self.visible_order();
+        if let Some(&idx) = order.get(self.selected) {
+            let is_dir = self
+                .current()
+                .children
+                .as_ref()
+                .map(|children| children[idx].children.is_some())
+                .unwrap_or(false);
+            if is_dir {
+                self.stack.push(idx);
+                self.selected = 0;
+            }
+        }
+    }
+
+    /// Go back up to the parent directory, if any.
+    pub fn ascend(&mut self) {
+        match self.stack.pop() {
+            Some(idx) => {
+                let order = self.visible_order();
+                self.selected = order.iter().position(|&i| i == idx).unwrap_or(0);
+            }
+            None => self.selected = 0,
+        }
+    }
+
+    pub fn toggle_sort(&mut self) {
+        self.sort_by = match self.sort_by {
+            SortBy::Size => SortBy::Name,
+            SortBy::Name => SortBy::Size,
+        };
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::env;
+use tui::style::Color;
+
+/// Maps file extensions to a display color, parsed from the `LS_COLORS`
+/// environment variable (the same `*.ext=SGR` format `ls`/`exa` use).
+pub struct LsColors {
+    by_extension: HashMap<String, Color>,
+}
+
+impl LsColors {
+    pub fn from_env() -> Self {
+        LsColors::parse(&env::var("LS_COLORS").unwrap_or_default())
+    }
+
+    fn parse(raw: &str) -> Self {
+        let mut by_extension = HashMap::new();
+        for entry in raw.split(':') {
+            let mut parts = entry.splitn(2, '=');
+            let pattern = match parts.next() {
+                Some(pattern) if pattern.starts_with("*.") => &pattern[2..],
+                _ => continue,
+            };
+            let sgr = match parts.next() {
+                Some(sgr) => sgr,
+                None => continue,
+            };
+            if let Some(color) = sgr_to_color(sgr) {
+                by_extension.insert(pattern.to_lowercase(), color);
+            }
+        }
+        LsColors { by_extension }
+    }
+
+    /// The color registered for `name`'s extension, if any.
+    pub fn color_for(&self, name: &str) -> Option<Color> {
+        let ext = &name[name.rfind('.')? + 1..];
+        self.by_extension.get(&ext.to_lowercase()).copied()
+    }
+}
+
+/// Pick out the foreground color from a semicolon-separated SGR sequence
+/// such as `01;31` (bold red).
+fn sgr_to_color(sgr: &str) -> Option<Color> {
+    sgr.split(';')
+        .filter_map(|code| code.parse::<u8>().ok())
+        .find_map(|code| match code {
+            30 => Some(Color::Black),
+            31 => Some(Color::Red),
+            32 => Some(Color::Green),
+            33 => Some(Color::Yellow),
+            34 => Some(Color::Blue),
+            35 => Some(Color::Magenta),
+            36 => Some(Color::Cyan),
+            37 => Some(Color::White),
+            90 => Some(Color::DarkGray),
+            91 => Some(Color::LightRed),
+            92 => Some(Color::LightGreen),
+            93 => Some(Color::LightYellow),
+            94 => Some(Color::LightBlue),
+            95 => Some(Color::LightMagenta),
+            96 => Some(Color::LightCyan),
+            97 => Some(Color::Gray),
+            _ => None,
+        })
+}
+
+/// A green -> yellow -> red heat color for how large `bytes` is relative
+/// to `max_bytes`, the largest entry currently in view.
+pub fn heat_color(bytes: u64, max_bytes: u64) -> Color {
+    let ratio = if max_bytes == 0 {
+        0.0
+    } else {
+        bytes as f64 / max_bytes as f64
+    };
+
+    if ratio >= 0.8 {
+        Color::Red
+    } else if ratio >= 0.5 {
+        Color::Yellow
+    } else {
+        Color::Green
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sgr_to_color_picks_the_foreground_code() {
+        assert_eq!(sgr_to_color("01;31"), Some(Color::Red));
+        assert_eq!(sgr_to_color("32"), Some(Color::Green));
+    }
+
+    #[test]
+    fn sgr_to_color_handles_bright_variants() {
+        assert_eq!(sgr_to_color("01;91"), Some(Color::LightRed));
+    }
+
+    #[test]
+    fn sgr_to_color_rejects_unknown_or_empty() {
+        assert_eq!(sgr_to_color(""), None);
+        assert_eq!(sgr_to_color("01;99"), None);
+        assert_eq!(sgr_to_color("not-a-code"), None);
+    }
+
+    #[test]
+    fn heat_color_buckets_by_ratio_to_max() {
+        assert_eq!(heat_color(90, 100), Color::Red);
+        assert_eq!(heat_color(60, 100), Color::Yellow);
+        assert_eq!(heat_color(10, 100), Color::Green);
+    }
+
+    #[test]
+    fn heat_color_treats_zero_max_as_coldest() {
+        assert_eq!(heat_color(0, 0), Color::Green);
+    }
+}
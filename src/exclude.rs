@@ -0,0 +1,23 @@
+use glob::Pattern;
+
+/// A set of glob patterns matched against a file's *name* (not its full
+/// path), used to skip files/directories via `--exclude`.
+pub struct ExcludeSet {
+    patterns: Vec<Pattern>,
+}
+
+impl ExcludeSet {
+    pub fn new(patterns: &[String]) -> Self {
+        ExcludeSet {
+            patterns: patterns
+                .iter()
+                .map(|pattern| Pattern::new(pattern).expect("Exclude pattern is invalid!"))
+                .collect(),
+        }
+    }
+
+    /// Whether `name` matches any of the configured exclude patterns.
+    pub fn matches(&self, name: &str) -> bool {
+        self.patterns.iter().any(|pattern| pattern.matches(name))
+    }
+}
@@ -0,0 +1,159 @@
+use number_prefix::NumberPrefix;
+use serde::Serialize;
+use serde_json;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+/// A single file's record in an export: its path, raw byte size, a
+/// human-readable rendering, and what percentage it is of the grand total.
+#[derive(Debug, Serialize)]
+pub struct Record {
+    pub path: String,
+    pub size_bytes: u64,
+    pub size_human: String,
+    pub percent: f64,
+}
+
+/// The shape an export written by `--output-file` takes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Csv,
+    Json,
+    Ndjson,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Format::from_extension(s).ok_or_else(|| format!("unknown format `{}`", s))
+    }
+}
+
+impl Format {
+    /// Infer a format from a file extension such as `json` or `ndjson`,
+    /// returning `None` for anything unrecognized.
+    pub fn from_extension(ext: &str) -> Option<Format> {
+        match ext.to_lowercase().as_str() {
+            "csv" => Some(Format::Csv),
+            "json" => Some(Format::Json),
+            "ndjson" | "jsonl" => Some(Format::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+pub fn size_human_readable(bytes: u64) -> String {
+    match NumberPrefix::decimal(bytes as f64) {
+        NumberPrefix::Standalone(bytes) => format!("{} B", bytes),
+        NumberPrefix::Prefixed(prefix, n) => format!("{:.*} {}B", 2, n, prefix),
+    }
+}
+
+/// Build the sorted (largest first) list of records for `entries`, each
+/// carrying its percentage of `total`.
+pub fn build_records(entries: &HashMap<String, u64>, total: u64) -> Vec<Record> {
+    let mut records: Vec<Record> = entries
+        .iter()
+        .map(|(path, &size_bytes)| Record {
+            path: path.clone(),
+            size_bytes,
+            size_human: size_human_readable(size_bytes),
+            percent: (size_bytes as f64 / total as f64) * 100_f64,
+        })
+        .collect();
+    records.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+    records
+}
+
+/// Write `entries` to `path` in `format`.
+///
+/// CSV and JSON need the full sorted list up front (CSV for stable row
+/// order, JSON for the wrapping `total_bytes` object), so they go through
+/// `build_records`. NDJSON's whole point is not needing that: it streams
+/// one record per line straight from `entries`, so a very large scan never
+/// has to hold every record in memory at once to write it out.
+pub fn write_records(
+    entries: &HashMap<String, u64>,
+    total: u64,
+    format: Format,
+    path: &Path,
+) -> Result<(), failure::Error> {
+    match format {
+        Format::Csv => write_csv(&build_records(entries, total), path),
+        Format::Json => write_json(&build_records(entries, total), total, path),
+        Format::Ndjson => write_ndjson(entries, total, path),
+    }
+}
+
+fn write_csv(records: &[Record], path: &Path) -> Result<(), failure::Error> {
+    let mut wtr = csv::Writer::from_path(path)?;
+    for record in records {
+        wtr.write_record(&[
+            record.path.clone(),
+            record.size_human.clone(),
+            format!("{:.*}%", 2, record.percent),
+        ])?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+fn write_json(records: &[Record], total: u64, path: &Path) -> Result<(), failure::Error> {
+    #[derive(Serialize)]
+    struct Export<'a> {
+        total_bytes: u64,
+        entries: &'a [Record],
+    }
+
+    let file = File::create(path)?;
+    serde_json::to_writer_pretty(file, &Export {
+        total_bytes: total,
+        entries: records,
+    })?;
+    Ok(())
+}
+
+fn write_ndjson(entries: &HashMap<String, u64>, total: u64, path: &Path) -> Result<(), failure::Error> {
+    let mut wtr = BufWriter::new(File::create(path)?);
+    for (entry_path, &size_bytes) in entries {
+        let record = Record {
+            path: entry_path.clone(),
+            size_bytes,
+            size_human: size_human_readable(size_bytes),
+            percent: (size_bytes as f64 / total as f64) * 100_f64,
+        };
+        serde_json::to_writer(&mut wtr, &record)?;
+        wtr.write_all(b"\n")?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_extension_recognizes_known_formats() {
+        assert_eq!(Format::from_extension("csv"), Some(Format::Csv));
+        assert_eq!(Format::from_extension("json"), Some(Format::Json));
+        assert_eq!(Format::from_extension("ndjson"), Some(Format::Ndjson));
+        assert_eq!(Format::from_extension("jsonl"), Some(Format::Ndjson));
+    }
+
+    #[test]
+    fn from_extension_is_case_insensitive() {
+        assert_eq!(Format::from_extension("CSV"), Some(Format::Csv));
+        assert_eq!(Format::from_extension("Json"), Some(Format::Json));
+    }
+
+    #[test]
+    fn from_extension_rejects_unknown() {
+        assert_eq!(Format::from_extension("txt"), None);
+        assert_eq!(Format::from_extension(""), None);
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::fs::Metadata;
+
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+
+/// Tracks `(device, inode)` pairs so that multiple hardlinks to the same
+/// file are only counted once towards the running total.
+#[derive(Debug, Default)]
+pub struct InodeFilter {
+    seen: HashMap<(u64, u64), usize>,
+}
+
+impl InodeFilter {
+    pub fn new() -> Self {
+        InodeFilter::default()
+    }
+
+    /// Record `metadata`'s inode, returning `true` the first time it is
+    /// seen (it should be counted) and `false` for every subsequent
+    /// hardlink to the same inode.
+    #[cfg(unix)]
+    pub fn should_count(&mut self, metadata: &Metadata) -> bool {
+        if metadata.nlink() <= 1 {
+            return true;
+        }
+
+        let key = (metadata.dev(), metadata.ino());
+        let count = self.seen.entry(key).or_insert(0);
+        *count += 1;
+        *count == 1
+    }
+
+    #[cfg(not(unix))]
+    pub fn should_count(&mut self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    /// Number of hardlinked entries that were collapsed out of the totals.
+    pub fn collapsed(&self) -> usize {
+        self.seen.values().filter(|&&count| count > 1).map(|count| count - 1).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collapsed_counts_extra_hardlinks_per_inode() {
+        let mut filter = InodeFilter::new();
+        filter.seen.insert((1, 100), 3);
+        filter.seen.insert((1, 200), 1);
+        assert_eq!(filter.collapsed(), 2);
+    }
+
+    #[test]
+    fn collapsed_is_zero_when_nothing_seen() {
+        let filter = InodeFilter::new();
+        assert_eq!(filter.collapsed(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_count_counts_first_hardlink_and_skips_rest() {
+        use std::fs;
+
+        let dir = std::env::temp_dir().join(format!("wbf-inode-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let original = dir.join("a");
+        let link = dir.join("b");
+        fs::write(&original, b"x").unwrap();
+        fs::hard_link(&original, &link).unwrap();
+
+        let mut filter = InodeFilter::new();
+        let meta_a = fs::metadata(&original).unwrap();
+        let meta_b = fs::metadata(&link).unwrap();
+        assert!(filter.should_count(&meta_a));
+        assert!(!filter.should_count(&meta_b));
+        assert_eq!(filter.collapsed(), 1);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,19 +1,32 @@
-use csv;
 use failure::Error;
-use number_prefix::NumberPrefix;
 use regex::Regex;
-use std::{collections::HashMap, fs, io, path::PathBuf};
+use std::{collections::HashMap, fs, io, path::PathBuf, sync::mpsc, thread};
 use structopt::StructOpt;
-use termion::{input::MouseTerminal, raw::IntoRawMode, screen::AlternateScreen};
+use termion::{
+    event::{Event, Key, MouseButton, MouseEvent},
+    input::{MouseTerminal, TermRead},
+    raw::IntoRawMode,
+    screen::AlternateScreen,
+};
 use tui::{
     backend::TermionBackend,
     layout::{Constraint, Layout},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     widgets::{Block, Borders, Row, Table, Widget},
     Terminal,
 };
 use walkdir::WalkDir;
 
+mod app;
+mod color;
+mod exclude;
+mod format;
+mod inode;
+mod size;
+mod tree;
+
+use app::App;
+
 #[derive(StructOpt, Debug)]
 #[structopt(name = "wbf", about = "What big file?")]
 struct Opt {
@@ -33,33 +46,88 @@ struct Opt {
     #[structopt(short, long)]
     filter: Option<String>,
 
+    /// Exclude files/directories whose name (not full path) matches this
+    /// glob; may be passed multiple times
+    #[structopt(short = "x", long = "exclude")]
+    exclude: Vec<String>,
+
+    /// Skip files/directories whose name begins with `.`
+    #[structopt(short = "H", long = "no-hidden")]
+    no_hidden: bool,
+
     /// Minimum file size to search for in bytes
     #[structopt(short, long)]
     min_size: Option<u64>,
 
-    /// Output CSV file
+    /// Output file (format inferred from extension unless `--format` is set)
     #[structopt(short, long, parse(from_os_str))]
     output_file: Option<PathBuf>,
-}
 
-fn main() -> Result<(), Error> {
-    let opt = Opt::from_args();
-    // Terminal initialization
-    let stdout = io::stdout().into_raw_mode()?;
-    let stdout = MouseTerminal::from(stdout);
-    let stdout = AlternateScreen::from(stdout);
-    let backend = TermionBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-    terminal.hide_cursor()?;
+    /// Output format for `--output-file`: csv, json, or ndjson
+    #[structopt(long)]
+    format: Option<format::Format>,
+
+    /// Render a hierarchical directory tree instead of a flat file list; the
+    /// whole path is walked and `--depth` only limits how many levels print
+    #[structopt(short, long)]
+    tree: bool,
+
+    /// Aggregation threshold for `--tree` (e.g. `10K`, `5M`, `1G`); entries
+    /// smaller than this are collapsed into a `<N files>` entry. Requires
+    /// `--tree`; it has no effect in the interactive view.
+    #[structopt(long, parse(try_from_str = tree::parse_threshold), requires = "tree")]
+    aggr: Option<u64>,
+
+    /// Report real on-disk (allocated) usage instead of apparent file length
+    #[structopt(short, long)]
+    usage: bool,
+
+    /// Count every hardlink separately instead of deduplicating shared inodes
+    #[structopt(long)]
+    count_hardlinks: bool,
+
+    /// Disable the file-type and size-heat row colors (for dumb terminals or
+    /// plain redirection); rows are drawn in white and the highlighted row
+    /// in the interactive view stays yellow and bold
+    #[structopt(long = "ascii", alias = "no-color")]
+    ascii: bool,
+}
 
+/// Walk `opt.path`, returning every matched file's size keyed by its
+/// (symlink-resolved) path, the running total, and the number of
+/// hardlinked entries collapsed out of that total.
+///
+/// `limit_depth` controls whether `--depth` bounds the walk itself. The
+/// flat/interactive views want that (they render exactly what was
+/// collected), but `--tree` rolls every file's size up into its ancestor
+/// directories and must collect everything regardless of `--depth`, which
+/// only bounds how deep `tree::render` prints.
+fn collect_entries(opt: &Opt, limit_depth: bool) -> (HashMap<String, u64>, u64, usize) {
     let regex_opt = opt
         .filter
         .clone()
         .map(|filter| Regex::new(&filter).expect("Regex is invalid!"));
+    let exclude_set = exclude::ExcludeSet::new(&opt.exclude);
     let walker = WalkDir::new(&opt.path)
         .follow_links(!opt.disable_symlinks)
         .into_iter()
         .filter_entry(|entry| {
+            let name = entry.file_name().to_str();
+
+            if opt.no_hidden {
+                if let Some(name) = name {
+                    if name.starts_with('.') && entry.depth() > 0 {
+                        return false;
+                    }
+                }
+            }
+
+            if let Some(name) = name {
+                if exclude_set.matches(name) {
+                    return false;
+                }
+            }
+
             if let Some(regex) = &regex_opt {
                 if let Some(path) = entry.path().to_str() {
                     !regex.is_match(path)
@@ -73,12 +141,15 @@ fn main() -> Result<(), Error> {
 
     let mut total = 0;
     let mut entries = HashMap::new();
+    let mut inode_filter = inode::InodeFilter::new();
     for entry_res in walker {
         if let Ok(entry) = entry_res {
             // Break when we get too deep
-            if let Some(depth) = opt.depth {
-                if depth > 0 && entry.depth() > depth {
-                    break;
+            if limit_depth {
+                if let Some(depth) = opt.depth {
+                    if depth > 0 && entry.depth() > depth {
+                        break;
+                    }
                 }
             }
 
@@ -99,74 +170,210 @@ fn main() -> Result<(), Error> {
             };
 
             if let (Some(path), Ok(metadata)) = (realpath.to_str(), entry.metadata()) {
-                let size = metadata.len();
+                let size = size::of(realpath, &metadata, opt.usage);
                 if size < opt.min_size.unwrap_or(0) {
                     continue;
                 }
 
+                if !opt.count_hardlinks && !inode_filter.should_count(&metadata) {
+                    continue;
+                }
+
                 total += size;
                 entries.insert(String::from(path), size);
             }
         }
+    }
+
+    (entries, total, inode_filter.collapsed())
+}
+
+/// Resolve the export format (explicit `--format`, else inferred from the
+/// output path's extension, defaulting to CSV) and write `entries` to
+/// `output_file` accordingly.
+fn write_output(
+    output_file: &PathBuf,
+    format: Option<format::Format>,
+    entries: &HashMap<String, u64>,
+    total: u64,
+) -> Result<(), Error> {
+    let format = format.unwrap_or_else(|| {
+        output_file
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(format::Format::from_extension)
+            .unwrap_or(format::Format::Csv)
+    });
+
+    format::write_records(entries, total, format, output_file)?;
+
+    Ok(())
+}
+
+fn print_hardlink_note(collapsed: usize) {
+    if collapsed > 0 {
+        println!(
+            "Note: collapsed {} hardlinked entr{} sharing an already-counted inode",
+            collapsed,
+            if collapsed == 1 { "y" } else { "ies" }
+        );
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let opt = Opt::from_args();
+
+    if opt.tree {
+        return run_tree_mode(&opt);
+    }
+
+    let (entries, total, collapsed) = collect_entries(&opt, true);
+
+    // A scripted export shouldn't have to wait behind a human quitting the
+    // full-screen TUI, so short-circuit to the file write and skip the
+    // interactive session entirely when `--output-file` is set.
+    if let Some(output_file) = &opt.output_file {
+        write_output(output_file, opt.format, &entries, total)?;
+        print_hardlink_note(collapsed);
+        return Ok(());
+    }
+
+    let root = tree::build_tree(&opt.path, &entries);
+    let mut app = App::new(root);
+    let ls_colors = color::LsColors::from_env();
+
+    // Terminal initialization
+    let stdout = io::stdout().into_raw_mode()?;
+    let stdout = MouseTerminal::from(stdout);
+    let stdout = AlternateScreen::from(stdout);
+    let backend = TermionBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+    terminal.hide_cursor()?;
+
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let stdin = io::stdin();
+        for event in stdin.events() {
+            if let Ok(event) = event {
+                if tx.send(event).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    loop {
+        let size = terminal.size()?;
+        let rects = Layout::default()
+            .constraints([Constraint::Percentage(100)].as_ref())
+            .margin(5)
+            .split(size);
+        let table_rect = rects[0];
 
         terminal.draw(|mut f| {
-            // let selected_style = Style::default().fg(Color::Yellow).modifier(Modifier::BOLD);
             let normal_style = Style::default().fg(Color::White);
-            let header = ["File", "Size", "Percentage of Total"];
-            let mut sorted_entries = entries.iter().collect::<Vec<_>>();
-            sorted_entries.sort_by(|(.., a_size_bytes), (.., b_size_bytes)| {
-                (**b_size_bytes).partial_cmp(&**a_size_bytes).unwrap()
-            });
-            let rows = sorted_entries.iter().map(|(path, size_bytes)| {
-                let size_human_readable = match NumberPrefix::decimal(**size_bytes as f64) {
-                    NumberPrefix::Standalone(bytes) => format!("{} B", bytes),
-                    NumberPrefix::Prefixed(prefix, n) => format!("{:.*} {}B", 2, n, prefix),
+            let selected_style = Style::default().fg(Color::Yellow).modifier(Modifier::BOLD);
+            let header = ["Name", "Size", "% of Dir"];
+
+            let order = app.visible_order();
+            let current = app.current();
+            let children = current.children.as_ref().map(Vec::as_slice).unwrap_or(&[]);
+            let parent_bytes = current.bytes.max(1);
+            let max_bytes = children.iter().map(|child| child.bytes).max().unwrap_or(0);
+
+            let rows = order.iter().enumerate().map(|(row, &idx)| {
+                let child = &children[idx];
+                let name = if child.children.is_some() {
+                    format!("{}/", child.name)
+                } else {
+                    child.name.clone()
+                };
+                let percent = (child.bytes as f64 / parent_bytes as f64) * 100_f64;
+                let style = if row == app.selected {
+                    selected_style
+                } else if opt.ascii {
+                    normal_style
+                } else {
+                    // The table only supports one style per row, so the
+                    // LS_COLORS extension color takes priority; the
+                    // green->yellow->red size-heat gradient only shows
+                    // through for entries whose extension has no mapping.
+                    let heat = color::heat_color(child.bytes, max_bytes);
+                    let fg = ls_colors.color_for(&child.name).unwrap_or(heat);
+                    Style::default().fg(fg)
                 };
 
                 Row::StyledData(
                     vec![
-                        String::from(&(*path).clone()),
-                        size_human_readable,
-                        format!("{:.*}%", 2, (**size_bytes as f64 / total as f64) * 100_f64),
+                        name,
+                        format::size_human_readable(child.bytes),
+                        format!("{:.*}%", 2, percent),
                     ]
                     .into_iter(),
-                    normal_style,
+                    style,
                 )
             });
 
-            let rects = Layout::default()
-                .constraints([Constraint::Percentage(100)].as_ref())
-                .margin(5)
-                .split(f.size());
             Table::new(header.iter(), rows)
-                .block(Block::default().borders(Borders::ALL).title("Table"))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(&app.breadcrumb()),
+                )
                 .widths(&[200, 10, 10])
-                .render(&mut f, rects[0]);
-        })?
+                .render(&mut f, table_rect);
+        })?;
+
+        match rx.recv() {
+            Ok(Event::Key(Key::Char('q'))) => break,
+            Ok(Event::Key(Key::Up)) | Ok(Event::Key(Key::Char('k'))) => app.move_selection(-1),
+            Ok(Event::Key(Key::Down)) | Ok(Event::Key(Key::Char('j'))) => app.move_selection(1),
+            Ok(Event::Key(Key::Char('\n'))) => app.descend(),
+            Ok(Event::Key(Key::Backspace)) => app.ascend(),
+            Ok(Event::Key(Key::Char('s'))) => app.toggle_sort(),
+            Ok(Event::Mouse(MouseEvent::Press(MouseButton::Left, _, y))) => {
+                // The block border (1) and header row (1) sit above the
+                // first data row within the table rect.
+                let data_start = table_rect.y + 2;
+                if y >= data_start {
+                    let row = (y - data_start) as usize;
+                    app.select(row);
+                    app.descend();
+                }
+            }
+            Ok(Event::Mouse(MouseEvent::Press(MouseButton::WheelUp, ..))) => {
+                app.move_selection(-1)
+            }
+            Ok(Event::Mouse(MouseEvent::Press(MouseButton::WheelDown, ..))) => {
+                app.move_selection(1)
+            }
+            Ok(_) => {}
+            Err(_) => break,
+        }
     }
 
-    if let Some(output_file) = opt.output_file {
-        let mut wtr = csv::Writer::from_path(output_file)?;
-        let mut sorted_entries = entries.iter().collect::<Vec<_>>();
-        sorted_entries.sort_by(|(.., a_size_bytes), (.., b_size_bytes)| {
-            (**b_size_bytes).partial_cmp(&**a_size_bytes).unwrap()
-        });
-        sorted_entries.iter().for_each(|(path, size_bytes)| {
-            let size_human_readable = match NumberPrefix::decimal(**size_bytes as f64) {
-                NumberPrefix::Standalone(bytes) => format!("{} B", bytes),
-                NumberPrefix::Prefixed(prefix, n) => format!("{:.*} {}B", 2, n, prefix),
-            };
+    drop(terminal);
 
-            wtr.write_record(&[
-                String::from(&(*path).clone()),
-                size_human_readable,
-                format!("{:.*}%", 2, (**size_bytes as f64 / total as f64) * 100_f64),
-            ])
-            .unwrap();
-        });
+    print_hardlink_note(collapsed);
 
-        wtr.flush()?;
+    Ok(())
+}
+
+/// Walk `opt.path` and print a disk-usage tree instead of the interactive
+/// TUI explorer, in the spirit of `dutree`.
+fn run_tree_mode(opt: &Opt) -> Result<(), Error> {
+    let (entries, _total, collapsed) = collect_entries(opt, false);
+
+    let mut root = tree::build_tree(&opt.path, &entries);
+    if let Some(threshold) = opt.aggr {
+        root.collapse(threshold);
     }
 
+    for line in tree::render(&root, opt.depth) {
+        println!("{}", line);
+    }
+
+    print_hardlink_note(collapsed);
+
     Ok(())
 }
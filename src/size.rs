@@ -0,0 +1,49 @@
+use std::fs::Metadata;
+use std::path::Path;
+
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+
+#[cfg(windows)]
+use std::{ffi::OsStr, os::windows::ffi::OsStrExt};
+
+/// Return the size of the file at `path`. When `usage` is `true`, report
+/// the real on-disk allocation rather than the apparent (logical) length
+/// reported by `metadata.len()`, falling back to `len()` when the
+/// allocated size can't be determined.
+pub fn of(path: &Path, metadata: &Metadata, usage: bool) -> u64 {
+    if usage {
+        allocated_size(path, metadata).unwrap_or_else(|| metadata.len())
+    } else {
+        metadata.len()
+    }
+}
+
+#[cfg(unix)]
+fn allocated_size(_path: &Path, metadata: &Metadata) -> Option<u64> {
+    Some(metadata.st_blocks() * 512)
+}
+
+#[cfg(windows)]
+fn allocated_size(path: &Path, _metadata: &Metadata) -> Option<u64> {
+    use winapi::um::fileapi::GetCompressedFileSizeW;
+
+    let wide: Vec<u16> = OsStr::new(path)
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+
+    let mut high: u32 = 0;
+    let low = unsafe { GetCompressedFileSizeW(wide.as_ptr(), &mut high) };
+
+    if low == u32::max_value() {
+        None
+    } else {
+        Some((u64::from(high) << 32) | u64::from(low))
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn allocated_size(_path: &Path, _metadata: &Metadata) -> Option<u64> {
+    None
+}
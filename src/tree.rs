@@ -0,0 +1,241 @@
+use crate::format::size_human_readable;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A node in the aggregated size tree: either a file leaf (`children` is
+/// `None`) or a directory with its own children, sorted by `bytes`
+/// descending.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub name: String,
+    pub bytes: u64,
+    pub children: Option<Vec<Entry>>,
+}
+
+impl Entry {
+    fn leaf(name: String, bytes: u64) -> Self {
+        Entry {
+            name,
+            bytes,
+            children: None,
+        }
+    }
+
+    fn dir(name: String) -> Self {
+        Entry {
+            name,
+            bytes: 0,
+            children: Some(Vec::new()),
+        }
+    }
+
+    fn sort(&mut self) {
+        if let Some(children) = &mut self.children {
+            children.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+            for child in children.iter_mut() {
+                child.sort();
+            }
+        }
+    }
+
+    /// Collapse children whose size falls below `threshold` into a single
+    /// synthetic `<N files>` entry at each level.
+    pub fn collapse(&mut self, threshold: u64) {
+        if let Some(children) = &mut self.children {
+            for child in children.iter_mut() {
+                child.collapse(threshold);
+            }
+
+            let (keep, small): (Vec<Entry>, Vec<Entry>) =
+                children.drain(..).partition(|entry| entry.bytes >= threshold);
+
+            *children = keep;
+
+            if !small.is_empty() {
+                let collapsed_bytes: u64 = small.iter().map(|entry| entry.bytes).sum();
+                children.push(Entry::leaf(
+                    format!("<{} files>", small.len()),
+                    collapsed_bytes,
+                ));
+                children.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+            }
+        }
+    }
+}
+
+/// Parse a human size threshold such as `10K`, `5M` or `1G` into bytes.
+/// Accepts a bare number (bytes) or a number followed by a `B`/`K`/`M`/`G`
+/// suffix (case-insensitive).
+pub fn parse_threshold(input: &str) -> Result<u64, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("empty threshold".to_string());
+    }
+
+    let (digits, multiplier) = match input.chars().last() {
+        Some(suffix) if suffix.is_ascii_alphabetic() => {
+            let multiplier = match suffix.to_ascii_uppercase() {
+                'B' => 1,
+                'K' => 1024,
+                'M' => 1024 * 1024,
+                'G' => 1024 * 1024 * 1024,
+                _ => return Err(format!("unknown size suffix: {}", suffix)),
+            };
+            (&input[..input.len() - 1], multiplier)
+        }
+        _ => (input, 1),
+    };
+
+    let n = digits
+        .parse::<u64>()
+        .map_err(|err| format!("invalid size `{}`: {}", input, err))?;
+
+    n.checked_mul(multiplier)
+        .ok_or_else(|| format!("size `{}` overflows u64", input))
+}
+
+/// Build a directory tree rooted at `root`, rolling each file's size up
+/// into every ancestor directory.
+pub fn build_tree(root: &Path, entries: &HashMap<String, u64>) -> Entry {
+    let root_name = root
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| root.to_string_lossy().into_owned());
+    let mut tree = Entry::dir(root_name);
+
+    for (path, &bytes) in entries {
+        let relative = Path::new(path).strip_prefix(root).unwrap_or(Path::new(path));
+        let components: Vec<String> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+
+        tree.bytes += bytes;
+        insert(&mut tree, &components, bytes);
+    }
+
+    tree.sort();
+    tree
+}
+
+fn insert(node: &mut Entry, components: &[String], bytes: u64) {
+    let children = node.children.get_or_insert_with(Vec::new);
+
+    match components.split_first() {
+        Some((head, rest)) if !rest.is_empty() => {
+            let child = match children.iter_mut().find(|entry| &entry.name == head) {
+                Some(existing) => existing,
+                None => {
+                    children.push(Entry::dir(head.clone()));
+                    children.last_mut().unwrap()
+                }
+            };
+            child.bytes += bytes;
+            insert(child, rest, bytes);
+        }
+        Some((head, _)) => children.push(Entry::leaf(head.clone(), bytes)),
+        None => {}
+    }
+}
+
+/// Render the tree as indented lines, each carrying its size and
+/// percentage of its parent, stopping at `max_depth` levels (`None` means
+/// unlimited).
+pub fn render(entry: &Entry, max_depth: Option<usize>) -> Vec<String> {
+    let mut lines = Vec::new();
+    lines.push(format!("{} ({})", entry.name, size_human_readable(entry.bytes)));
+    render_children(entry, 1, max_depth, &mut lines);
+    lines
+}
+
+fn render_children(entry: &Entry, depth: usize, max_depth: Option<usize>, lines: &mut Vec<String>) {
+    if let Some(max_depth) = max_depth {
+        if max_depth > 0 && depth > max_depth {
+            return;
+        }
+    }
+
+    if let Some(children) = &entry.children {
+        let parent_bytes = entry.bytes.max(1);
+        for child in children {
+            let indent = "  ".repeat(depth);
+            let percent = (child.bytes as f64 / parent_bytes as f64) * 100_f64;
+            lines.push(format!(
+                "{}{} ({}, {:.2}%)",
+                indent,
+                child.name,
+                size_human_readable(child.bytes),
+                percent
+            ));
+            render_children(child, depth + 1, max_depth, lines);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_threshold_bare_number_is_bytes() {
+        assert_eq!(parse_threshold("100").unwrap(), 100);
+    }
+
+    #[test]
+    fn parse_threshold_accepts_suffixes() {
+        assert_eq!(parse_threshold("10K").unwrap(), 10 * 1024);
+        assert_eq!(parse_threshold("5M").unwrap(), 5 * 1024 * 1024);
+        assert_eq!(parse_threshold("1G").unwrap(), 1024 * 1024 * 1024);
+        assert_eq!(parse_threshold("3b").unwrap(), 3);
+    }
+
+    #[test]
+    fn parse_threshold_rejects_empty() {
+        assert!(parse_threshold("").is_err());
+        assert!(parse_threshold("   ").is_err());
+    }
+
+    #[test]
+    fn parse_threshold_rejects_garbage() {
+        assert!(parse_threshold("abc").is_err());
+        assert!(parse_threshold("10X").is_err());
+    }
+
+    #[test]
+    fn parse_threshold_rejects_overflow() {
+        assert!(parse_threshold("18000000000000000G").is_err());
+    }
+
+    #[test]
+    fn collapse_rolls_small_entries_into_a_placeholder() {
+        let mut dir = Entry::dir("root".to_string());
+        let children = dir.children.as_mut().unwrap();
+        children.push(Entry::leaf("big.bin".to_string(), 1000));
+        children.push(Entry::leaf("a.txt".to_string(), 10));
+        children.push(Entry::leaf("b.txt".to_string(), 20));
+
+        dir.collapse(100);
+
+        let children = dir.children.unwrap();
+        assert_eq!(children.len(), 2);
+        assert!(children
+            .iter()
+            .any(|entry| entry.name == "big.bin" && entry.bytes == 1000));
+        assert!(children
+            .iter()
+            .any(|entry| entry.name == "<2 files>" && entry.bytes == 30));
+    }
+
+    #[test]
+    fn collapse_is_a_no_op_when_nothing_is_below_threshold() {
+        let mut dir = Entry::dir("root".to_string());
+        dir.children
+            .as_mut()
+            .unwrap()
+            .push(Entry::leaf("big.bin".to_string(), 1000));
+
+        dir.collapse(100);
+
+        assert_eq!(dir.children.unwrap().len(), 1);
+    }
+}